@@ -0,0 +1,423 @@
+//! Proc-macro companion to `mcproto-rs`'s `define_protocol!` family. These
+//! derives generate the same `Serialize`/`Deserialize`/`Packet` impls (and
+//! `ProtocolSpec::describe()` output) that the `macro_rules` macros produce,
+//! but from real items with real spans and per-field attributes.
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{
+    parse_macro_input, Data, DeriveInput, Fields, Ident, Lit, Meta, MetaNameValue, NestedMeta,
+};
+
+/// Per-field wire framing, set via `#[mc(...)]`. Defaults to the field's own
+/// `Serialize`/`Deserialize` impl when no attribute is present.
+enum FieldKind {
+    Default,
+    VarInt,
+    Rest,
+}
+
+fn field_kind(field: &syn::Field) -> FieldKind {
+    for attr in &field.attrs {
+        if !attr.path.is_ident("mc") {
+            continue;
+        }
+        if let Ok(Meta::List(list)) = attr.parse_meta() {
+            for item in list.nested {
+                if let NestedMeta::Meta(Meta::Path(path)) = item {
+                    if path.is_ident("varint") {
+                        return FieldKind::VarInt;
+                    }
+                    if path.is_ident("rest") {
+                        return FieldKind::Rest;
+                    }
+                }
+            }
+        }
+    }
+    FieldKind::Default
+}
+
+fn struct_fields(data: &Data) -> &syn::FieldsNamed {
+    let fields = match data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => fields,
+            _ => panic!("McSerialize/McDeserialize only support structs with named fields"),
+        },
+        _ => panic!("McSerialize/McDeserialize only support structs, use #[derive(Packet)] for enums"),
+    };
+
+    // `#[mc(rest)]` hands everything left in the buffer to its field, so a
+    // field after it would always deserialize from an empty slice while
+    // serializing normally — a silent asymmetry rather than a compile error.
+    let last = fields.named.len().saturating_sub(1);
+    for (i, field) in fields.named.iter().enumerate() {
+        if matches!(field_kind(field), FieldKind::Rest) && i != last {
+            panic!(
+                "#[mc(rest)] is only valid on a struct's last field; `{}` consumes the rest of the buffer, leaving nothing for the fields after it",
+                field.ident.as_ref().expect("named field")
+            );
+        }
+    }
+
+    fields
+}
+
+fn serialize_field(name: &Ident, kind: &FieldKind) -> TokenStream2 {
+    match kind {
+        FieldKind::Default => quote! { to.serialize_other(&self.#name)?; },
+        FieldKind::VarInt => quote! { to.serialize_other(&::mcproto_rs::VarInt(self.#name as i32))?; },
+        FieldKind::Rest => quote! { to.serialize_bytes(&self.#name)?; },
+    }
+}
+
+fn deserialize_field(name: &Ident, ty: &syn::Type, kind: &FieldKind) -> TokenStream2 {
+    match kind {
+        FieldKind::Default => quote! {
+            let ::mcproto_rs::Deserialized { value: #name, data: _rest } = <#ty as ::mcproto_rs::Deserialize>::mc_deserialize(_rest)?;
+        },
+        FieldKind::VarInt => quote! {
+            let ::mcproto_rs::Deserialized { value: #name, data: _rest } = ::mcproto_rs::VarInt::mc_deserialize(_rest)?;
+            let #name = #name.0 as #ty;
+        },
+        FieldKind::Rest => quote! {
+            let #name = <#ty>::from(_rest.to_vec());
+            let _rest: &[u8] = &[];
+        },
+    }
+}
+
+/// The wire-facing "kind" a field is described as in a generated
+/// `describe()`, mirroring how `define_protocol!` stringifies `$ftyp`.
+fn field_kind_name(kind: &FieldKind, ty: &syn::Type) -> TokenStream2 {
+    match kind {
+        FieldKind::VarInt => quote! { "VarInt" },
+        _ => {
+            let ty_str = quote!(#ty).to_string();
+            quote! { #ty_str }
+        }
+    }
+}
+
+#[proc_macro_derive(McSerialize, attributes(mc))]
+pub fn derive_mc_serialize(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+    let fields = struct_fields(&input.data);
+
+    let body = fields.named.iter().map(|field| {
+        let kind = field_kind(field);
+        let fname = field.ident.as_ref().expect("named field");
+        serialize_field(fname, &kind)
+    });
+
+    let describe_fields = fields.named.iter().map(|field| {
+        let kind = field_kind(field);
+        let fname = field.ident.as_ref().expect("named field");
+        let fkind = field_kind_name(&kind, &field.ty);
+        quote! {
+            ::mcproto_rs::protocol::ProtocolPacketField {
+                name: stringify!(#fname).to_owned(),
+                kind: #fkind.to_owned(),
+            }
+        }
+    });
+
+    let expanded = quote! {
+        impl ::mcproto_rs::Serialize for #name {
+            fn mc_serialize<S: ::mcproto_rs::Serializer>(&self, to: &mut S) -> ::mcproto_rs::SerializeResult {
+                #(#body)*
+                Ok(())
+            }
+        }
+
+        impl #name {
+            /// The per-field `(name, kind)` pairs this struct's `McSerialize`
+            /// derive generated, used by `#[derive(Packet)]`'s `describe()`
+            /// to reflect the same field list `define_protocol!` produces.
+            pub fn describe_fields() -> ::std::vec::Vec<::mcproto_rs::protocol::ProtocolPacketField> {
+                vec![#(#describe_fields),*]
+            }
+        }
+    };
+
+    expanded.into()
+}
+
+#[proc_macro_derive(McDeserialize, attributes(mc))]
+pub fn derive_mc_deserialize(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+    let fields = struct_fields(&input.data);
+
+    let fnames: Vec<_> = fields
+        .named
+        .iter()
+        .map(|field| field.ident.clone().expect("named field"))
+        .collect();
+    let body = fields.named.iter().map(|field| {
+        let kind = field_kind(field);
+        let fname = field.ident.as_ref().expect("named field");
+        deserialize_field(fname, &field.ty, &kind)
+    });
+
+    let expanded = quote! {
+        impl ::mcproto_rs::Deserialize for #name {
+            fn mc_deserialize(_rest: &[u8]) -> ::mcproto_rs::DeserializeResult<'_, Self> {
+                #(#body)*
+                ::mcproto_rs::Deserialized::ok(Self { #(#fnames),* }, _rest)
+            }
+        }
+    };
+
+    expanded.into()
+}
+
+/// The id/state/direction types a protocol's `#[derive(Packet)]` enum
+/// dispatches on, set once via a container-level `#[packet(id_type = "...",
+/// state_type = "...", direction_type = "...")]`. These are the types
+/// `define_protocol!` takes as its `$idt`/`$statet`/`$directiont` generic
+/// parameters — the derive can't assume a fixed path for them since every
+/// protocol version defines its own.
+struct PacketTypes {
+    id: syn::Type,
+    state: syn::Type,
+    direction: syn::Type,
+}
+
+fn parse_type_lit(lit: &Lit, key: &str) -> syn::Type {
+    match lit {
+        Lit::Str(s) => syn::parse_str::<syn::Type>(&s.value())
+            .unwrap_or_else(|err| panic!("#[packet({} = ...)] is not a valid type: {}", key, err)),
+        other => panic!("#[packet({} = ...)] must be a string literal, got {:?}", key, other),
+    }
+}
+
+fn packet_types(attrs: &[syn::Attribute]) -> PacketTypes {
+    let mut id = None;
+    let mut state = None;
+    let mut direction = None;
+
+    for attr in attrs {
+        if !attr.path.is_ident("packet") {
+            continue;
+        }
+        if let Ok(Meta::List(list)) = attr.parse_meta() {
+            for item in list.nested {
+                if let NestedMeta::Meta(Meta::NameValue(MetaNameValue { path, lit, .. })) = item {
+                    if path.is_ident("id_type") {
+                        id = Some(parse_type_lit(&lit, "id_type"));
+                    } else if path.is_ident("state_type") {
+                        state = Some(parse_type_lit(&lit, "state_type"));
+                    } else if path.is_ident("direction_type") {
+                        direction = Some(parse_type_lit(&lit, "direction_type"));
+                    }
+                }
+            }
+        }
+    }
+
+    PacketTypes {
+        id: id.expect("#[derive(Packet)] enum is missing #[packet(id_type = \"...\")]"),
+        state: state.expect("#[derive(Packet)] enum is missing #[packet(state_type = \"...\")]"),
+        direction: direction
+            .expect("#[derive(Packet)] enum is missing #[packet(direction_type = \"...\")]"),
+    }
+}
+
+struct PacketAttr {
+    id: syn::Expr,
+    state: Ident,
+    direction: Ident,
+}
+
+fn packet_attr(attrs: &[syn::Attribute]) -> PacketAttr {
+    let mut id = None;
+    let mut state = None;
+    let mut direction = None;
+
+    for attr in attrs {
+        if !attr.path.is_ident("packet") {
+            continue;
+        }
+        if let Ok(Meta::List(list)) = attr.parse_meta() {
+            for item in list.nested {
+                if let NestedMeta::Meta(Meta::NameValue(MetaNameValue { path, lit, .. })) = item {
+                    if path.is_ident("id") {
+                        id = Some(match lit {
+                            Lit::Int(int) => syn::parse_str::<syn::Expr>(&int.to_string()).unwrap(),
+                            other => panic!("#[packet(id = ...)] must be an integer literal, got {:?}", other),
+                        });
+                    } else if path.is_ident("state") {
+                        state = Some(match lit {
+                            Lit::Str(s) => format_ident!("{}", s.value()),
+                            other => panic!("#[packet(state = ...)] must be a string literal, got {:?}", other),
+                        });
+                    } else if path.is_ident("direction") {
+                        direction = Some(match lit {
+                            Lit::Str(s) => format_ident!("{}", s.value()),
+                            other => panic!("#[packet(direction = ...)] must be a string literal, got {:?}", other),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    PacketAttr {
+        id: id.expect("variant is missing #[packet(id = ...)]"),
+        state: state.expect("variant is missing #[packet(state = ...)]"),
+        direction: direction.expect("variant is missing #[packet(direction = ...)]"),
+    }
+}
+
+/// `#[derive(Packet)]` on an enum whose variants each wrap a single body
+/// type and carry `#[packet(id = 0x00, state = "Handshaking", direction =
+/// "ServerBound")]`. The enum itself carries `#[packet(id_type = "Id",
+/// state_type = "State", direction_type = "Direction")]` naming the
+/// protocol's own id/state/direction types (the ones `define_protocol!`
+/// takes as `$idt`/`$statet`/`$directiont`). Produces the same
+/// `Packet::id`/`mc_deserialize` dispatch, `Serialize` forwarding, and
+/// `describe()` that `define_protocol!` generates, so definitions can
+/// migrate one enum at a time. Each body type must itself derive
+/// `McSerialize`/`McDeserialize` so its `describe_fields()` is available.
+#[proc_macro_derive(Packet, attributes(packet))]
+pub fn derive_packet(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+    let types = packet_types(&input.attrs);
+    let (id_ty, state_ty, direction_ty) = (&types.id, &types.state, &types.direction);
+
+    let data = match &input.data {
+        Data::Enum(data) => data,
+        _ => panic!("#[derive(Packet)] only supports enums"),
+    };
+
+    struct Variant<'a> {
+        name: &'a Ident,
+        body: &'a syn::Type,
+        attr: PacketAttr,
+    }
+
+    let variants: Vec<Variant> = data
+        .variants
+        .iter()
+        .map(|variant| {
+            let body = match &variant.fields {
+                Fields::Unnamed(fields) if fields.unnamed.len() == 1 => &fields.unnamed[0].ty,
+                _ => panic!("#[derive(Packet)] variants must wrap exactly one body type"),
+            };
+            Variant {
+                name: &variant.ident,
+                body,
+                attr: packet_attr(&variant.attrs),
+            }
+        })
+        .collect();
+
+    let id_arms = variants.iter().map(|v| {
+        let (vname, id, state, direction) = (v.name, &v.attr.id, &v.attr.state, &v.attr.direction);
+        quote! { #name::#vname(_) => (#id, #state_ty::#state, #direction_ty::#direction) }
+    });
+
+    let deserialize_arms = variants.iter().map(|v| {
+        let (vname, id, state, direction, body) =
+            (v.name, &v.attr.id, &v.attr.state, &v.attr.direction, v.body);
+        quote! {
+            (#id, #state_ty::#state, #direction_ty::#direction) =>
+                Ok(#name::#vname(#body::mc_deserialize(data).map_err(::mcproto_rs::protocol::PacketErr::DeserializeFailed)?.value))
+        }
+    });
+
+    let serialize_arms = variants.iter().map(|v| {
+        let vname = v.name;
+        quote! { #name::#vname(body) => to.serialize_other(body) }
+    });
+
+    let describe_entries = variants.iter().map(|v| {
+        let (vname, id, state, direction, body) =
+            (v.name, &v.attr.id, &v.attr.state, &v.attr.direction, v.body);
+        quote! {
+            ::mcproto_rs::protocol::ProtocolPacketSpec {
+                state: stringify!(#state).to_owned(),
+                direction: stringify!(#direction).to_owned(),
+                id: #id,
+                name: stringify!(#vname).to_owned(),
+                body_struct: stringify!(#body).to_owned(),
+                fields: #body::describe_fields(),
+            }
+        }
+    });
+
+    let expanded = quote! {
+        impl ::mcproto_rs::protocol::Packet<#id_ty> for #name {
+            fn id(&self) -> #id_ty {
+                match self {
+                    #(#id_arms),*
+                }.into()
+            }
+
+            fn mc_deserialize(raw: ::mcproto_rs::protocol::RawPacket<'_, #id_ty>) -> Result<Self, ::mcproto_rs::protocol::PacketErr> {
+                use ::mcproto_rs::Deserialize;
+
+                let id = raw.id;
+                let data = raw.data;
+
+                match (id.id, id.state, id.direction) {
+                    #(#deserialize_arms),*,
+                    other => Err(::mcproto_rs::protocol::PacketErr::UnknownId(other.0)),
+                }
+            }
+        }
+
+        impl ::mcproto_rs::Serialize for #name {
+            fn mc_serialize<S: ::mcproto_rs::Serializer>(&self, to: &mut S) -> ::mcproto_rs::SerializeResult {
+                match self {
+                    #(#serialize_arms),*
+                }
+            }
+        }
+
+        impl #name {
+            pub fn describe() -> ::mcproto_rs::protocol::ProtocolSpec {
+                ::mcproto_rs::protocol::ProtocolSpec {
+                    name: stringify!(#name).to_owned(),
+                    packets: vec![#(#describe_entries),*],
+                }
+            }
+        }
+    };
+
+    expanded.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::DeriveInput;
+
+    fn parse_fields(src: &str) -> syn::FieldsNamed {
+        let input: DeriveInput = syn::parse_str(src).expect("valid struct");
+        struct_fields(&input.data).clone()
+    }
+
+    #[test]
+    #[should_panic(expected = "only valid on a struct's last field")]
+    fn rejects_mc_rest_not_on_the_last_field() {
+        parse_fields("struct Body { #[mc(rest)] a: Vec<u8>, b: i32 }");
+    }
+
+    #[test]
+    fn allows_mc_rest_on_the_last_field() {
+        let fields = parse_fields("struct Body { a: i32, #[mc(rest)] b: Vec<u8> }");
+        assert_eq!(fields.named.len(), 2);
+    }
+
+    #[test]
+    fn allows_a_single_mc_rest_field() {
+        let fields = parse_fields("struct Body { #[mc(rest)] a: Vec<u8> }");
+        assert_eq!(fields.named.len(), 1);
+    }
+}
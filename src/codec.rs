@@ -0,0 +1,423 @@
+use crate::protocol::{Packet, PacketIdentifier};
+use crate::{DeserializeErr, Serialize, SerializeErr, VarInt};
+use aes::Aes128;
+use bytes::{BufMut, BytesMut};
+use cfb8::cipher::{AsyncStreamCipher, NewCipher};
+use cfb8::Cfb8;
+use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
+use std::io::{self, Read, Write};
+use tokio_util::codec::{Decoder, Encoder};
+
+/// Largest VarInt length prefix this codec will believe before allocating a
+/// buffer for it. Minecraft packets never legitimately exceed a few MiB; a
+/// bigger claimed length is either a corrupt stream or an attacker trying to
+/// force an unbounded allocation, so it's rejected outright.
+const MAX_PACKET_LEN: usize = 2 * 1024 * 1024;
+
+type Aes128Cfb8 = Cfb8<Aes128>;
+
+/// A length-prefixed frame read off (or about to be written to) the wire,
+/// after compression and encryption have already been undone/applied. The
+/// packet id here is the raw leading [`VarInt`] — turning it into a
+/// protocol-specific `Id` (with its `state`/`direction`) is left to the
+/// caller, who is the only one that knows what state the connection is in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawProtocolPacket {
+    pub id: i32,
+    pub data: Vec<u8>,
+}
+
+/// `tokio_util::codec::{Encoder, Decoder}` implementation for Minecraft's
+/// stream framing: a VarInt length prefix, an optional zlib-compressed
+/// inner frame once compression is enabled, and an optional AES-128-CFB8
+/// stream cipher wrapped around the whole thing once encryption is enabled.
+///
+/// Both compression and encryption are toggled mid-stream (compression
+/// after the server sends `SetCompression`, encryption after the key
+/// exchange completes), so this codec holds that state itself rather than
+/// taking it as a constructor argument. Because AES-CFB8 is a stateful
+/// stream cipher, incoming bytes are decrypted exactly once as they arrive
+/// and parked in `plaintext` until a full frame is available.
+pub struct PacketCodec {
+    compression_threshold: Option<i32>,
+    cipher: Option<(Aes128Cfb8, Aes128Cfb8)>,
+    plaintext: BytesMut,
+}
+
+impl Default for PacketCodec {
+    fn default() -> Self {
+        Self {
+            compression_threshold: None,
+            cipher: None,
+            plaintext: BytesMut::new(),
+        }
+    }
+}
+
+impl PacketCodec {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Switches framing into compressed mode; packets whose *uncompressed*
+    /// length is at least `threshold` will be deflated, and the decoder will
+    /// expect the compressed frame shape from this point on.
+    pub fn enable_compression(&mut self, threshold: i32) {
+        self.compression_threshold = Some(threshold);
+    }
+
+    pub fn disable_compression(&mut self) {
+        self.compression_threshold = None;
+    }
+
+    /// Switches both directions of the stream into AES-128-CFB8, keyed and
+    /// IV'd by the same 16-byte shared secret, as negotiated during login
+    /// encryption.
+    pub fn enable_encryption(&mut self, shared_secret: &[u8; 16]) {
+        let encryptor = Aes128Cfb8::new_from_slices(shared_secret, shared_secret)
+            .expect("16 byte key/iv is always valid for AES-128");
+        let decryptor = Aes128Cfb8::new_from_slices(shared_secret, shared_secret)
+            .expect("16 byte key/iv is always valid for AES-128");
+        self.cipher = Some((encryptor, decryptor));
+    }
+}
+
+impl PacketCodec {
+    /// Wraps an already-assembled `id + body` buffer in this codec's
+    /// current compression and encryption framing and appends the result
+    /// to `dst`. Shared by both `Encoder` impls below so the raw-bytes path
+    /// and the typed-`Packet` path can't drift apart.
+    fn write_frame(&mut self, body: Vec<u8>, dst: &mut BytesMut) -> Result<(), io::Error> {
+        let mut frame = Vec::new();
+        if let Some(threshold) = self.compression_threshold {
+            if body.len() as i32 >= threshold {
+                VarInt(body.len() as i32).mc_serialize(&mut frame)?;
+                let mut encoder = ZlibEncoder::new(&mut frame, Compression::default());
+                encoder.write_all(&body)?;
+                encoder.finish()?;
+            } else {
+                VarInt(0).mc_serialize(&mut frame)?;
+                frame.extend_from_slice(&body);
+            }
+        } else {
+            frame = body;
+        }
+
+        let mut out = Vec::new();
+        VarInt(frame.len() as i32).mc_serialize(&mut out)?;
+        out.extend_from_slice(&frame);
+
+        if let Some((encryptor, _)) = &mut self.cipher {
+            encryptor.encrypt(&mut out);
+        }
+        dst.put_slice(&out);
+        Ok(())
+    }
+}
+
+impl Encoder<RawProtocolPacket> for PacketCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, packet: RawProtocolPacket, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let mut body = Vec::new();
+        VarInt(packet.id).mc_serialize(&mut body)?;
+        body.extend_from_slice(&packet.data);
+        self.write_frame(body, dst)
+    }
+}
+
+/// Encodes an actual protocol `Packet` directly, rather than forcing the
+/// caller to flatten it into a [`RawProtocolPacket`] first: `packet.id()`
+/// and `packet` itself are serialized straight into the frame. Taken by
+/// reference since encoding never needs to consume the packet.
+///
+/// The reverse direction can't hand back the crate's [`RawPacket`](crate::protocol::RawPacket)
+/// the same way: it borrows its `data` for a lifetime tied to the input
+/// buffer, which `Decoder::Item` (a fixed associated type with no lifetime
+/// parameter of its own) can't express. Decoding therefore still produces
+/// an owned [`RawProtocolPacket`], leaving the caller to turn its raw id
+/// into this connection's typed `Id` and call `Packet::mc_deserialize`.
+impl<I: PacketIdentifier, P: Packet<I>> Encoder<&P> for PacketCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, packet: &P, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let mut body = Vec::new();
+        packet.id().mc_serialize(&mut body)?;
+        packet.mc_serialize(&mut body)?;
+        self.write_frame(body, dst)
+    }
+}
+
+impl Decoder for PacketCodec {
+    type Item = RawProtocolPacket;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        if !src.is_empty() {
+            let mut incoming = src.split_to(src.len());
+            if let Some((_, decryptor)) = &mut self.cipher {
+                decryptor.decrypt(&mut incoming);
+            }
+            self.plaintext.unsplit(incoming);
+        }
+
+        let (frame_len, prefix_len) = match read_varint_len(&self.plaintext)? {
+            Some(found) => found,
+            None => return Ok(None),
+        };
+
+        if frame_len > MAX_PACKET_LEN {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("refusing to allocate a {} byte packet frame", frame_len),
+            ));
+        }
+
+        if self.plaintext.len() < prefix_len + frame_len {
+            return Ok(None);
+        }
+
+        let mut raw = self.plaintext.split_to(prefix_len + frame_len);
+        let frame = raw.split_off(prefix_len);
+
+        let body = if self.compression_threshold.is_some() {
+            let crate::Deserialized {
+                value: uncompressed_len,
+                data: rest,
+            } = VarInt::mc_deserialize(&frame)?;
+            if uncompressed_len.0 == 0 {
+                rest.to_vec()
+            } else {
+                let uncompressed_len = uncompressed_len.0 as usize;
+                if uncompressed_len > MAX_PACKET_LEN {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("refusing to inflate a {} byte packet frame", uncompressed_len),
+                    ));
+                }
+
+                let mut inflated = Vec::with_capacity(uncompressed_len);
+                // `take` bounds the inflated output even if the advertised length
+                // lies and the stream keeps producing bytes (a zlib bomb).
+                ZlibDecoder::new(rest)
+                    .take(uncompressed_len as u64)
+                    .read_to_end(&mut inflated)?;
+                if inflated.len() != uncompressed_len {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "decompressed packet length did not match the advertised uncompressed length",
+                    ));
+                }
+                inflated
+            }
+        } else {
+            frame.to_vec()
+        };
+
+        let crate::Deserialized { value: id, data: rest } = VarInt::mc_deserialize(&body)?;
+        Ok(Some(RawProtocolPacket {
+            id: id.0,
+            data: rest.to_vec(),
+        }))
+    }
+}
+
+/// Reads a VarInt-prefixed frame length from the *front* of `src` without
+/// consuming anything, returning `(frame_len, bytes_used_by_the_prefix)`.
+/// `Ok(None)` means the buffer doesn't yet hold a complete VarInt.
+fn read_varint_len(src: &BytesMut) -> Result<Option<(usize, usize)>, io::Error> {
+    let mut value: i32 = 0;
+    for i in 0..5 {
+        if src.len() <= i {
+            return Ok(None);
+        }
+        let byte = src[i];
+        value |= ((byte & 0x7F) as i32) << (7 * i);
+        if byte & 0x80 == 0 {
+            if value < 0 {
+                return Err(io::Error::new(io::ErrorKind::InvalidData, "negative frame length"));
+            }
+            return Ok(Some((value as usize, i + 1)));
+        }
+    }
+    Err(io::Error::new(io::ErrorKind::InvalidData, "VarInt frame length is too long"))
+}
+
+impl From<DeserializeErr> for io::Error {
+    fn from(err: DeserializeErr) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, format!("{:?}", err))
+    }
+}
+
+impl From<SerializeErr> for io::Error {
+    fn from(err: SerializeErr) -> Self {
+        io::Error::new(io::ErrorKind::InvalidData, format!("{:?}", err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(codec_in: &mut PacketCodec, codec_out: &mut PacketCodec, packet: RawProtocolPacket) {
+        let mut buf = BytesMut::new();
+        codec_in.encode(packet.clone(), &mut buf).expect("encode");
+        let decoded = codec_out
+            .decode(&mut buf)
+            .expect("decode")
+            .expect("a full frame was written");
+        assert_eq!(decoded, packet);
+    }
+
+    #[test]
+    fn round_trips_without_compression_or_encryption() {
+        round_trip(
+            &mut PacketCodec::new(),
+            &mut PacketCodec::new(),
+            RawProtocolPacket {
+                id: 0x01,
+                data: vec![1, 2, 3, 4],
+            },
+        );
+    }
+
+    #[test]
+    fn round_trips_below_the_compression_threshold() {
+        let mut encoder = PacketCodec::new();
+        encoder.enable_compression(256);
+        let mut decoder = PacketCodec::new();
+        decoder.enable_compression(256);
+
+        round_trip(
+            &mut encoder,
+            &mut decoder,
+            RawProtocolPacket {
+                id: 0x02,
+                data: vec![5; 8],
+            },
+        );
+    }
+
+    #[test]
+    fn round_trips_above_the_compression_threshold() {
+        let mut encoder = PacketCodec::new();
+        encoder.enable_compression(16);
+        let mut decoder = PacketCodec::new();
+        decoder.enable_compression(16);
+
+        round_trip(
+            &mut encoder,
+            &mut decoder,
+            RawProtocolPacket {
+                id: 0x03,
+                data: vec![7; 512],
+            },
+        );
+    }
+
+    #[test]
+    fn round_trips_through_encryption() {
+        let shared_secret = [0x42; 16];
+        let mut encoder = PacketCodec::new();
+        encoder.enable_encryption(&shared_secret);
+        let mut decoder = PacketCodec::new();
+        decoder.enable_encryption(&shared_secret);
+
+        round_trip(
+            &mut encoder,
+            &mut decoder,
+            RawProtocolPacket {
+                id: 0x04,
+                data: vec![9; 32],
+            },
+        );
+    }
+
+    #[test]
+    fn buffers_a_split_frame_across_multiple_decode_calls() {
+        let mut encoder = PacketCodec::new();
+        let mut decoder = PacketCodec::new();
+        let packet = RawProtocolPacket {
+            id: 0x05,
+            data: vec![1, 2, 3, 4, 5, 6, 7, 8],
+        };
+
+        let mut whole = BytesMut::new();
+        encoder.encode(packet.clone(), &mut whole).expect("encode");
+        let split_at = whole.len() / 2;
+        let mut first_half = whole.split_to(split_at);
+        let mut second_half = whole;
+
+        assert_eq!(decoder.decode(&mut first_half).expect("decode"), None);
+        let decoded = decoder
+            .decode(&mut second_half)
+            .expect("decode")
+            .expect("the frame is complete once the second half arrives");
+        assert_eq!(decoded, packet);
+    }
+
+    #[test]
+    fn rejects_a_frame_length_over_the_max_packet_len() {
+        let mut decoder = PacketCodec::new();
+        let mut prefix = Vec::new();
+        VarInt(MAX_PACKET_LEN as i32 + 1)
+            .mc_serialize(&mut prefix)
+            .expect("serialize length prefix");
+        let mut src = BytesMut::new();
+        src.extend_from_slice(&prefix);
+
+        let err = decoder.decode(&mut src).expect_err("oversized frame length is rejected");
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    crate::proto_byte_enum!(CodecTestState,
+        0x00 :: Play
+    );
+
+    crate::proto_byte_enum!(CodecTestDirection,
+        0x00 :: ServerBound
+    );
+
+    crate::define_protocol!(CodecTestPacket, CodecTestDirection, CodecTestState, i32, CodecTestId => {
+        Ping, 0x01, Play, ServerBound => CodecTestPingBody { payload: i64 }
+    });
+
+    #[test]
+    fn encodes_a_typed_packet_the_same_as_its_raw_equivalent() {
+        let packet = CodecTestPacket::Ping(CodecTestPingBody { payload: 7 });
+
+        let mut typed_buf = BytesMut::new();
+        PacketCodec::new().encode(&packet, &mut typed_buf).expect("encode typed");
+
+        let mut body = Vec::new();
+        packet.mc_serialize(&mut body).expect("serialize body");
+        let raw = RawProtocolPacket { id: 0x01, data: body };
+        let mut raw_buf = BytesMut::new();
+        PacketCodec::new().encode(raw, &mut raw_buf).expect("encode raw");
+
+        assert_eq!(typed_buf, raw_buf);
+    }
+
+    #[test]
+    fn rejects_an_uncompressed_length_over_the_max_packet_len() {
+        let mut decoder = PacketCodec::new();
+        decoder.enable_compression(0);
+
+        let mut body = Vec::new();
+        VarInt(MAX_PACKET_LEN as i32 + 1)
+            .mc_serialize(&mut body)
+            .expect("serialize uncompressed length");
+
+        let mut frame = Vec::new();
+        VarInt(body.len() as i32).mc_serialize(&mut frame).expect("serialize frame length");
+        frame.extend_from_slice(&body);
+
+        let mut src = BytesMut::new();
+        src.extend_from_slice(&frame);
+
+        let err = decoder
+            .decode(&mut src)
+            .expect_err("oversized uncompressed length is rejected before inflating");
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
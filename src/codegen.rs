@@ -0,0 +1,327 @@
+use crate::protocol::{ProtocolPacketField, ProtocolPacketSpec, ProtocolSpec};
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// The handful of identifiers `define_protocol!` needs that aren't part of
+/// a [`ProtocolSpec`] itself (a spec only knows the *data*, not what the
+/// generated packet enum, id struct, state enum, etc. should be called).
+/// These stay the same across every protocol version a project vendors, so
+/// a `build.rs` typically hardcodes one `ProtocolTypeNames` and feeds it
+/// every spec file it regenerates from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProtocolTypeNames {
+    pub packet_enum: String,
+    pub direction_type: String,
+    pub state_type: String,
+    pub id_type: String,
+    pub id_struct: String,
+}
+
+/// Emits the `define_protocol! { ... }` invocation that produces the packet
+/// enum, `Id` struct, and body structs described by `spec`, so a protocol
+/// version can be maintained as a data file instead of a hand-written
+/// macro body. Intended to be called from a `build.rs` and the output
+/// written to `$OUT_DIR` for `include!()`.
+pub fn generate_source(spec: &ProtocolSpec, names: &ProtocolTypeNames) -> String {
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "mcproto_rs::define_protocol!({}, {}, {}, {}, {} => {{",
+        names.packet_enum, names.direction_type, names.state_type, names.id_type, names.id_struct
+    );
+
+    for (i, packet) in spec.packets.iter().enumerate() {
+        let comma = if i + 1 == spec.packets.len() { "" } else { "," };
+        let fields = packet
+            .fields
+            .iter()
+            .map(|field| format!("{}: {}", field.name, field.kind))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let _ = writeln!(
+            out,
+            "    {}, {:#x}, {}, {} => {} {{ {} }}{}",
+            packet.name, packet.id, packet.state, packet.direction, packet.body_struct, fields, comma
+        );
+    }
+
+    out.push_str("});\n");
+    out
+}
+
+/// Reads a JSON-encoded [`ProtocolSpec`] from `spec_path` and writes the
+/// generated `define_protocol!` invocation to `out_path`. Meant to be
+/// called straight from a crate's `build.rs`:
+///
+/// ```ignore
+/// mcproto_rs::codegen::generate_from_file(
+///     "protocol/v756.json",
+///     format!("{}/v756.rs", std::env::var("OUT_DIR").unwrap()),
+///     &names,
+/// ).unwrap();
+/// ```
+pub fn generate_from_file(
+    spec_path: impl AsRef<Path>,
+    out_path: impl AsRef<Path>,
+    names: &ProtocolTypeNames,
+) -> io::Result<()> {
+    let raw = fs::read_to_string(spec_path)?;
+    let spec: ProtocolSpec = serde_json::from_str(&raw)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    fs::write(out_path, generate_source(&spec, names))
+}
+
+/// Splits `s` on `sep` the way a Rust parser would split a field/argument
+/// list: only where `sep` isn't nested inside `<...>` or `(...)`. Needed
+/// because a field's `kind` can itself be a generic like
+/// `CountedArray<T, VarInt>`, whose inner comma isn't a field separator.
+fn split_top_level(s: &str, sep: char) -> impl Iterator<Item = &str> {
+    let mut depth = 0i32;
+    let mut start = 0;
+    let mut parts = Vec::new();
+    for (i, c) in s.char_indices() {
+        match c {
+            '<' | '(' => depth += 1,
+            '>' | ')' => depth -= 1,
+            c if c == sep && depth == 0 => {
+                parts.push(&s[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&s[start..]);
+    parts.into_iter()
+}
+
+/// Parses a `define_protocol!` invocation produced by [`generate_source`]
+/// back into the [`ProtocolSpec`] it was generated from. This only needs
+/// to understand the shape this module itself emits (not arbitrary
+/// `define_protocol!` bodies hand-written elsewhere in the crate), which is
+/// what makes a plain line-oriented parser sufficient instead of pulling in
+/// a full Rust parser.
+pub fn spec_from_generated_source(source: &str, packet_enum_name: &str) -> Option<ProtocolSpec> {
+    let mut lines = source.lines();
+    lines.next()?; // the `define_protocol!(...)` header line, names already known by the caller
+
+    let mut packets = Vec::new();
+    for line in lines {
+        let line = line.trim().trim_end_matches(',');
+        if line.is_empty() || line == "});" || line == "}" {
+            continue;
+        }
+
+        let (head, body) = line.split_once('{')?;
+        let body = body.trim_end_matches('}').trim();
+        let (left, body_struct) = head.rsplit_once("=>")?;
+        let mut head_parts = left.split(',');
+        let name = head_parts.next()?.trim().to_owned();
+        let id = i32::from_str_radix(head_parts.next()?.trim().trim_start_matches("0x"), 16).ok()?;
+        let state = head_parts.next()?.trim().to_owned();
+        let direction = head_parts.next()?.trim().to_owned();
+        let body_struct = body_struct.trim().to_owned();
+
+        let fields = if body.is_empty() {
+            Vec::new()
+        } else {
+            split_top_level(body, ',')
+                .map(|field| {
+                    let (name, kind) = field.trim().split_once(':').expect("field is name: type");
+                    ProtocolPacketField {
+                        name: name.trim().to_owned(),
+                        kind: kind.trim().to_owned(),
+                    }
+                })
+                .collect()
+        };
+
+        packets.push(ProtocolPacketSpec {
+            state,
+            direction,
+            id,
+            name,
+            body_struct,
+            fields,
+        });
+    }
+
+    Some(ProtocolSpec {
+        name: packet_enum_name.to_owned(),
+        packets,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Deserialize, DeserializeErr, DeserializeResult, Deserialized, Serialize, SerializeResult,
+        Serializer, TestRandom, VarInt,
+    };
+
+    // A protocol produced the same way a hand-written module would: through
+    // the real `define_protocol!` macro, not a hand-built `ProtocolSpec`.
+    // This is what lets the round-trip test below start from compiled types'
+    // `describe()` instead of only this module's own string writer/parser.
+    // The regenerated source is parsed back into a spec, not compiled.
+    crate::proto_byte_enum!(CodegenTestState,
+        0x00 :: Handshaking,
+        0x01 :: Status
+    );
+
+    crate::proto_byte_enum!(CodegenTestDirection,
+        0x00 :: ServerBound,
+        0x01 :: ClientBound
+    );
+
+    crate::define_protocol!(CodegenTestPacket, CodegenTestDirection, CodegenTestState, i32, CodegenTestId => {
+        Handshake, 0x00, Handshaking, ServerBound => CodegenTestHandshakeBody { protocol_version: VarInt, server_address: String },
+        StatusResponse, 0x00, Status, ClientBound => CodegenTestStatusResponseBody { }
+    });
+
+    /// Generates source from a real macro-generated enum's `describe()`,
+    /// regenerates a `ProtocolSpec` from that source, and checks it's
+    /// identical to what the compiled types actually describe themselves
+    /// as — the round trip the codegen module exists to support.
+    #[test]
+    fn round_trips_a_compiled_protocol_through_generated_source() {
+        let original_spec = CodegenTestPacket::describe();
+
+        let names = ProtocolTypeNames {
+            packet_enum: "CodegenTestPacket".to_owned(),
+            direction_type: "CodegenTestDirection".to_owned(),
+            state_type: "CodegenTestState".to_owned(),
+            id_type: "i32".to_owned(),
+            id_struct: "CodegenTestId".to_owned(),
+        };
+
+        let generated = generate_source(&original_spec, &names);
+        let regenerated_spec = spec_from_generated_source(&generated, &original_spec.name)
+            .expect("valid generated source");
+
+        assert_eq!(original_spec, regenerated_spec);
+    }
+
+    fn sample_spec() -> ProtocolSpec {
+        ProtocolSpec {
+            name: "TestPacket".to_owned(),
+            packets: vec![
+                ProtocolPacketSpec {
+                    state: "Handshaking".to_owned(),
+                    direction: "ServerBound".to_owned(),
+                    id: 0x00,
+                    name: "Handshake".to_owned(),
+                    body_struct: "HandshakeBody".to_owned(),
+                    fields: vec![
+                        ProtocolPacketField {
+                            name: "protocol_version".to_owned(),
+                            kind: "VarInt".to_owned(),
+                        },
+                        ProtocolPacketField {
+                            name: "server_address".to_owned(),
+                            kind: "String".to_owned(),
+                        },
+                    ],
+                },
+                ProtocolPacketSpec {
+                    state: "Status".to_owned(),
+                    direction: "ClientBound".to_owned(),
+                    id: 0x00,
+                    name: "StatusResponse".to_owned(),
+                    body_struct: "StatusResponseBody".to_owned(),
+                    fields: vec![],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn parses_a_generic_field_kind_containing_a_comma() {
+        let names = ProtocolTypeNames {
+            packet_enum: "TestPacket".to_owned(),
+            direction_type: "Direction".to_owned(),
+            state_type: "State".to_owned(),
+            id_type: "i32".to_owned(),
+            id_struct: "Id".to_owned(),
+        };
+        let spec = ProtocolSpec {
+            name: "TestPacket".to_owned(),
+            packets: vec![ProtocolPacketSpec {
+                state: "Play".to_owned(),
+                direction: "ServerBound".to_owned(),
+                id: 0x00,
+                name: "Chunk".to_owned(),
+                body_struct: "ChunkBody".to_owned(),
+                fields: vec![
+                    ProtocolPacketField {
+                        name: "sections".to_owned(),
+                        kind: "CountedArray<Section, VarInt>".to_owned(),
+                    },
+                    ProtocolPacketField {
+                        name: "trailing".to_owned(),
+                        kind: "i32".to_owned(),
+                    },
+                ],
+            }],
+        };
+
+        let source = generate_source(&spec, &names);
+        let regenerated = spec_from_generated_source(&source, &spec.name).expect("valid generated source");
+
+        assert_eq!(spec, regenerated);
+    }
+
+    /// Exercises the full `build.rs`-shaped path: a JSON spec file on disk,
+    /// generated into a `define_protocol!` source file, the way
+    /// `generate_from_file` is actually used.
+    #[test]
+    fn generates_from_a_json_spec_file_on_disk() {
+        let names = ProtocolTypeNames {
+            packet_enum: "TestPacket".to_owned(),
+            direction_type: "Direction".to_owned(),
+            state_type: "State".to_owned(),
+            id_type: "i32".to_owned(),
+            id_struct: "Id".to_owned(),
+        };
+        let spec = sample_spec();
+
+        let dir = std::env::temp_dir().join(format!(
+            "mcproto-rs-codegen-test-{}-{}",
+            std::process::id(),
+            "generates_from_a_json_spec_file_on_disk"
+        ));
+        fs::create_dir_all(&dir).expect("create temp dir");
+        let spec_path = dir.join("spec.json");
+        let out_path = dir.join("generated.rs");
+
+        fs::write(&spec_path, serde_json::to_string(&spec).expect("serialize spec")).expect("write spec file");
+
+        generate_from_file(&spec_path, &out_path, &names).expect("generate from file");
+
+        let generated = fs::read_to_string(&out_path).expect("read generated source");
+        let regenerated = spec_from_generated_source(&generated, &spec.name).expect("valid generated source");
+
+        assert_eq!(spec, regenerated);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn round_trips_through_generated_source() {
+        let names = ProtocolTypeNames {
+            packet_enum: "TestPacket".to_owned(),
+            direction_type: "Direction".to_owned(),
+            state_type: "State".to_owned(),
+            id_type: "i32".to_owned(),
+            id_struct: "Id".to_owned(),
+        };
+        let spec = sample_spec();
+
+        let source = generate_source(&spec, &names);
+        let regenerated = spec_from_generated_source(&source, &spec.name).expect("valid generated source");
+
+        assert_eq!(spec, regenerated);
+    }
+}
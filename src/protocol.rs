@@ -1,4 +1,4 @@
-use crate::{Deserialize, DeserializeErr, Serialize};
+use crate::{Deserialize, DeserializeErr, Serialize, SerializeErr, SerializeResult, Serializer};
 use std::fmt;
 
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -66,6 +66,43 @@ pub trait ProtocolType: Serialize + Deserialize {}
 
 impl<T: Serialize + Deserialize> ProtocolType for T {}
 
+/// A `Serializer` that only accumulates the number of bytes a value would
+/// write, without allocating a backing buffer. Lets a networking layer
+/// learn a packet's encoded length (e.g. to decide compressed vs.
+/// uncompressed framing) without serializing it twice.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CountingSerializer {
+    pub count: usize,
+}
+
+impl Serializer for CountingSerializer {
+    fn serialize_bytes(&mut self, bytes: &[u8]) -> SerializeResult {
+        self.count += bytes.len();
+        Ok(())
+    }
+
+    fn serialize_byte(&mut self, _byte: u8) -> SerializeResult {
+        self.count += 1;
+        Ok(())
+    }
+
+    fn serialize_other<S: Serialize>(&mut self, other: &S) -> SerializeResult {
+        other.mc_serialize(self)
+    }
+}
+
+/// Returns the number of bytes `packet` would occupy on the wire — its
+/// leading [`PacketIdentifier`] VarInt plus its body — without actually
+/// allocating the encoded buffer. `Packet::mc_serialize` only covers the
+/// body (the id is carried separately by [`Packet::id`]), so both are fed
+/// through the counter to match what a framing layer actually writes.
+pub fn serialized_len<I: PacketIdentifier, P: Packet<I>>(packet: &P) -> Result<usize, SerializeErr> {
+    let mut counter = CountingSerializer::default();
+    packet.id().mc_serialize(&mut counter)?;
+    packet.mc_serialize(&mut counter)?;
+    Ok(counter.count)
+}
+
 #[cfg(test)]
 pub trait TestRandom {
     fn test_gen_random() -> Self;
@@ -507,3 +544,32 @@ macro_rules! counted_array_type {
         }
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Deserialized, DeserializeResult};
+
+    crate::proto_byte_enum!(SerializedLenTestState,
+        0x00 :: Play
+    );
+
+    crate::proto_byte_enum!(SerializedLenTestDirection,
+        0x00 :: ServerBound
+    );
+
+    crate::define_protocol!(SerializedLenTestPacket, SerializedLenTestDirection, SerializedLenTestState, i32, SerializedLenTestId => {
+        Ping, 0x01, Play, ServerBound => SerializedLenTestPingBody { payload: i64 }
+    });
+
+    #[test]
+    fn serialized_len_includes_the_leading_id() {
+        let packet = SerializedLenTestPacket::Ping(SerializedLenTestPingBody { payload: 42 });
+
+        let mut buf = Vec::new();
+        packet.id().mc_serialize(&mut buf).unwrap();
+        packet.mc_serialize(&mut buf).unwrap();
+
+        assert_eq!(serialized_len(&packet).unwrap(), buf.len());
+    }
+}
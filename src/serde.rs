@@ -0,0 +1,675 @@
+use crate::{DeserializeErr, SerializeErr, VarInt};
+use serde::{de, ser};
+use std::fmt;
+
+/// Writes serde's data model onto the wire using the same conventions as
+/// [`crate::Serialize`]: fixed-width big-endian integers, a single 0/1 byte
+/// for `bool`, a [`VarInt`] byte-length prefix ahead of `str` data, a
+/// [`VarInt`] element-count prefix ahead of seq elements, and a [`VarInt`]
+/// discriminant ahead of enum variant bodies.
+#[derive(Debug, Default)]
+pub struct Serializer {
+    out: Vec<u8>,
+}
+
+impl Serializer {
+    pub fn new() -> Self {
+        Self { out: Vec::new() }
+    }
+
+    pub fn into_inner(self) -> Vec<u8> {
+        self.out
+    }
+}
+
+/// Serializes `value` the way a hand-written [`crate::Serialize`] impl
+/// would, returning the encoded bytes.
+pub fn to_bytes<T: ser::Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, SerdeErr> {
+    let mut serializer = Serializer::new();
+    value.serialize(&mut serializer)?;
+    Ok(serializer.into_inner())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SerdeErr {
+    Custom(String),
+    UnsupportedType(&'static str),
+}
+
+impl fmt::Display for SerdeErr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SerdeErr::Custom(msg) => f.write_str(msg),
+            SerdeErr::UnsupportedType(name) => {
+                f.write_fmt(format_args!("{} has no wire representation", name))
+            }
+        }
+    }
+}
+
+impl std::error::Error for SerdeErr {}
+
+impl ser::Error for SerdeErr {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        SerdeErr::Custom(msg.to_string())
+    }
+}
+
+impl de::Error for SerdeErr {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        SerdeErr::Custom(msg.to_string())
+    }
+}
+
+impl From<DeserializeErr> for SerdeErr {
+    fn from(err: DeserializeErr) -> Self {
+        SerdeErr::Custom(format!("{:?}", err))
+    }
+}
+
+impl From<SerializeErr> for SerdeErr {
+    fn from(err: SerializeErr) -> Self {
+        SerdeErr::Custom(format!("{:?}", err))
+    }
+}
+
+macro_rules! serialize_int {
+    ($method: ident, $typ: ty) => {
+        fn $method(self, v: $typ) -> Result<Self::Ok, Self::Error> {
+            self.out.extend_from_slice(&v.to_be_bytes());
+            Ok(())
+        }
+    };
+}
+
+impl<'a> ser::Serializer for &'a mut Serializer {
+    type Ok = ();
+    type Error = SerdeErr;
+
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
+        self.out.push(if v { 1 } else { 0 });
+        Ok(())
+    }
+
+    serialize_int!(serialize_i8, i8);
+    serialize_int!(serialize_i16, i16);
+    serialize_int!(serialize_i32, i32);
+    serialize_int!(serialize_i64, i64);
+    serialize_int!(serialize_u8, u8);
+    serialize_int!(serialize_u16, u16);
+    serialize_int!(serialize_u32, u32);
+    serialize_int!(serialize_u64, u64);
+    serialize_int!(serialize_f32, f32);
+    serialize_int!(serialize_f64, f64);
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
+        VarInt(v.len() as i32).mc_serialize(&mut self.out)?;
+        self.out.extend_from_slice(v.as_bytes());
+        Ok(())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
+        VarInt(v.len() as i32).mc_serialize(&mut self.out)?;
+        self.out.extend_from_slice(v);
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        self.serialize_bool(false)
+    }
+
+    fn serialize_some<T: ?Sized + ser::Serialize>(self, value: &T) -> Result<Self::Ok, Self::Error> {
+        self.serialize_bool(true)?;
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        VarInt(variant_index as i32).mc_serialize(&mut self.out)?;
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + ser::Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + ser::Serialize>(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        VarInt(variant_index as i32).mc_serialize(&mut self.out)?;
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        let len = len.ok_or(SerdeErr::UnsupportedType("sequence with unknown length"))?;
+        VarInt(len as i32).mc_serialize(&mut self.out)?;
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        VarInt(variant_index as i32).mc_serialize(&mut self.out)?;
+        Ok(self)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Err(SerdeErr::UnsupportedType("map"))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        VarInt(variant_index as i32).mc_serialize(&mut self.out)?;
+        Ok(self)
+    }
+}
+
+impl<'a> ser::SerializeSeq for &'a mut Serializer {
+    type Ok = ();
+    type Error = SerdeErr;
+
+    fn serialize_element<T: ?Sized + ser::Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeTuple for &'a mut Serializer {
+    type Ok = ();
+    type Error = SerdeErr;
+
+    fn serialize_element<T: ?Sized + ser::Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeTupleStruct for &'a mut Serializer {
+    type Ok = ();
+    type Error = SerdeErr;
+
+    fn serialize_field<T: ?Sized + ser::Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeTupleVariant for &'a mut Serializer {
+    type Ok = ();
+    type Error = SerdeErr;
+
+    fn serialize_field<T: ?Sized + ser::Serialize>(&mut self, value: &T) -> Result<(), Self::Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeMap for &'a mut Serializer {
+    type Ok = ();
+    type Error = SerdeErr;
+
+    fn serialize_key<T: ?Sized + ser::Serialize>(&mut self, _key: &T) -> Result<(), Self::Error> {
+        Err(SerdeErr::UnsupportedType("map"))
+    }
+
+    fn serialize_value<T: ?Sized + ser::Serialize>(&mut self, _value: &T) -> Result<(), Self::Error> {
+        Err(SerdeErr::UnsupportedType("map"))
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Err(SerdeErr::UnsupportedType("map"))
+    }
+}
+
+impl<'a> ser::SerializeStruct for &'a mut Serializer {
+    type Ok = ();
+    type Error = SerdeErr;
+
+    fn serialize_field<T: ?Sized + ser::Serialize>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeStructVariant for &'a mut Serializer {
+    type Ok = ();
+    type Error = SerdeErr;
+
+    fn serialize_field<T: ?Sized + ser::Serialize>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), Self::Error> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+}
+
+/// Borrows from `&'de [u8]` and reads serde's data model back off the wire,
+/// mirroring [`Serializer`]'s framing conventions.
+#[derive(Debug)]
+pub struct Deserializer<'de> {
+    rest: &'de [u8],
+}
+
+impl<'de> Deserializer<'de> {
+    pub fn from_bytes(data: &'de [u8]) -> Self {
+        Self { rest: data }
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'de [u8], SerdeErr> {
+        if self.rest.len() < n {
+            return Err(DeserializeErr::Eof.into());
+        }
+        let (taken, rest) = self.rest.split_at(n);
+        self.rest = rest;
+        Ok(taken)
+    }
+
+    fn take_varint(&mut self) -> Result<i32, SerdeErr> {
+        let crate::Deserialized { value, data } = VarInt::mc_deserialize(self.rest)?;
+        self.rest = data;
+        Ok(value.0)
+    }
+}
+
+/// Deserializes a `T` from `data` the way a hand-written
+/// [`crate::Deserialize`] impl would.
+pub fn from_bytes<'de, T: de::Deserialize<'de>>(data: &'de [u8]) -> Result<T, SerdeErr> {
+    let mut deserializer = Deserializer::from_bytes(data);
+    T::deserialize(&mut deserializer)
+}
+
+macro_rules! deserialize_int {
+    ($method: ident, $visit: ident, $typ: ty) => {
+        fn $method<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+            let bytes = self.take(std::mem::size_of::<$typ>())?;
+            let mut buf = [0u8; std::mem::size_of::<$typ>()];
+            buf.copy_from_slice(bytes);
+            visitor.$visit(<$typ>::from_be_bytes(buf))
+        }
+    };
+}
+
+impl<'de, 'a> de::Deserializer<'de> for &'a mut Deserializer<'de> {
+    type Error = SerdeErr;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(SerdeErr::UnsupportedType(
+            "self-describing format (wire encoding requires a known schema)",
+        ))
+    }
+
+    fn deserialize_bool<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let b = self.take(1)?[0];
+        visitor.visit_bool(b != 0)
+    }
+
+    deserialize_int!(deserialize_i8, visit_i8, i8);
+    deserialize_int!(deserialize_i16, visit_i16, i16);
+    deserialize_int!(deserialize_i32, visit_i32, i32);
+    deserialize_int!(deserialize_i64, visit_i64, i64);
+    deserialize_int!(deserialize_u8, visit_u8, u8);
+    deserialize_int!(deserialize_u16, visit_u16, u16);
+    deserialize_int!(deserialize_u32, visit_u32, u32);
+    deserialize_int!(deserialize_u64, visit_u64, u64);
+    deserialize_int!(deserialize_f32, visit_f32, f32);
+    deserialize_int!(deserialize_f64, visit_f64, f64);
+
+    fn deserialize_char<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let s = self.read_str()?;
+        let c = s
+            .chars()
+            .next()
+            .ok_or_else(|| SerdeErr::Custom("expected a single char, got empty string".to_owned()))?;
+        visitor.visit_char(c)
+    }
+
+    fn deserialize_str<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_borrowed_str(self.read_str()?)
+    }
+
+    fn deserialize_string<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_string(self.read_str()?.to_owned())
+    }
+
+    fn deserialize_bytes<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let len = self.take_varint()? as usize;
+        visitor.visit_borrowed_bytes(self.take(len)?)
+    }
+
+    fn deserialize_byte_buf<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let len = self.take_varint()? as usize;
+        visitor.visit_byte_buf(self.take(len)?.to_vec())
+    }
+
+    fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let present = self.take(1)?[0] != 0;
+        if present {
+            visitor.visit_some(self)
+        } else {
+            visitor.visit_none()
+        }
+    }
+
+    fn deserialize_unit<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let len = self.take_varint()? as usize;
+        visitor.visit_seq(SeqAccess { de: self, remaining: len })
+    }
+
+    fn deserialize_tuple<V: de::Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_seq(SeqAccess { de: self, remaining: len })
+    }
+
+    fn deserialize_tuple_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_seq(SeqAccess { de: self, remaining: len })
+    }
+
+    fn deserialize_map<V: de::Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(SerdeErr::UnsupportedType("map"))
+    }
+
+    fn deserialize_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_seq(SeqAccess {
+            de: self,
+            remaining: fields.len(),
+        })
+    }
+
+    fn deserialize_enum<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_enum(EnumAccess { de: self })
+    }
+
+    fn deserialize_identifier<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_u32(self.take_varint()? as u32)
+    }
+
+    fn deserialize_ignored_any<V: de::Visitor<'de>>(self, _visitor: V) -> Result<V::Value, Self::Error> {
+        Err(SerdeErr::UnsupportedType("ignored_any"))
+    }
+}
+
+impl<'de> Deserializer<'de> {
+    fn read_str(&mut self) -> Result<&'de str, SerdeErr> {
+        let len = self.take_varint()? as usize;
+        let bytes = self.take(len)?;
+        std::str::from_utf8(bytes).map_err(|err| SerdeErr::Custom(err.to_string()))
+    }
+}
+
+struct SeqAccess<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+    remaining: usize,
+}
+
+impl<'a, 'de> de::SeqAccess<'de> for SeqAccess<'a, 'de> {
+    type Error = SerdeErr;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.remaining)
+    }
+}
+
+struct EnumAccess<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+}
+
+impl<'a, 'de> de::EnumAccess<'de> for EnumAccess<'a, 'de> {
+    type Error = SerdeErr;
+    type Variant = Self;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant), Self::Error> {
+        let value = seed.deserialize(&mut *self.de)?;
+        Ok((value, self))
+    }
+}
+
+impl<'a, 'de> de::VariantAccess<'de> for EnumAccess<'a, 'de> {
+    type Error = SerdeErr;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Self::Error> {
+        seed.deserialize(self.de)
+    }
+
+    fn tuple_variant<V: de::Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error> {
+        de::Deserializer::deserialize_tuple(self.de, len, visitor)
+    }
+
+    fn struct_variant<V: de::Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        de::Deserializer::deserialize_struct(self.de, "", fields, visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ints_and_bool_round_trip_as_fixed_width_big_endian() {
+        assert_eq!(to_bytes(&42i32).unwrap(), 42i32.to_be_bytes().to_vec());
+        assert_eq!(to_bytes(&(-7i64)).unwrap(), (-7i64).to_be_bytes().to_vec());
+        assert_eq!(to_bytes(&200u8).unwrap(), 200u8.to_be_bytes().to_vec());
+        assert_eq!(to_bytes(&true).unwrap(), vec![1]);
+        assert_eq!(to_bytes(&false).unwrap(), vec![0]);
+
+        assert_eq!(from_bytes::<i32>(&to_bytes(&42i32).unwrap()).unwrap(), 42i32);
+        assert_eq!(from_bytes::<i64>(&to_bytes(&(-7i64)).unwrap()).unwrap(), -7i64);
+        assert_eq!(from_bytes::<u8>(&to_bytes(&200u8).unwrap()).unwrap(), 200u8);
+        assert!(from_bytes::<bool>(&to_bytes(&true).unwrap()).unwrap());
+    }
+
+    /// `str` is length-prefixed with the same [`VarInt`] the rest of the
+    /// crate's hand-rolled `Serialize` impls use, so the prefix is checked
+    /// directly against `VarInt::mc_serialize` rather than just round-tripped.
+    #[test]
+    fn str_is_prefixed_with_a_varint_byte_length_like_the_hand_rolled_impls() {
+        let value = "hello";
+
+        let mut expected = Vec::new();
+        VarInt(value.len() as i32).mc_serialize(&mut expected).unwrap();
+        expected.extend_from_slice(value.as_bytes());
+
+        assert_eq!(to_bytes(&value).unwrap(), expected);
+        assert_eq!(from_bytes::<String>(&expected).unwrap(), value);
+    }
+
+    #[test]
+    fn option_is_a_presence_byte_followed_by_the_value() {
+        let some: Option<i32> = Some(9);
+        let none: Option<i32> = None;
+
+        assert_eq!(to_bytes(&some).unwrap(), vec![1, 0, 0, 0, 9]);
+        assert_eq!(to_bytes(&none).unwrap(), vec![0]);
+
+        assert_eq!(from_bytes::<Option<i32>>(&to_bytes(&some).unwrap()).unwrap(), some);
+        assert_eq!(from_bytes::<Option<i32>>(&to_bytes(&none).unwrap()).unwrap(), none);
+    }
+
+    /// `Vec`/seq is length-prefixed with the same [`VarInt`] convention as
+    /// `str`, checked directly against `VarInt::mc_serialize` rather than
+    /// just round-tripped.
+    #[test]
+    fn vec_is_prefixed_with_a_varint_element_count_like_the_hand_rolled_impls() {
+        let value: Vec<i32> = vec![1, 2, 3];
+
+        let mut expected = Vec::new();
+        VarInt(value.len() as i32).mc_serialize(&mut expected).unwrap();
+        for element in &value {
+            expected.extend_from_slice(&element.to_be_bytes());
+        }
+
+        assert_eq!(to_bytes(&value).unwrap(), expected);
+        assert_eq!(from_bytes::<Vec<i32>>(&expected).unwrap(), value);
+    }
+
+    #[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
+    enum TestEnum {
+        First,
+        Second(i32),
+        Third,
+    }
+
+    /// Enum variants are identified by a [`VarInt`] discriminant, the same
+    /// way `define_protocol!`'s generated packet enums identify themselves
+    /// by a `VarInt`-encoded id — checked directly against
+    /// `VarInt::mc_serialize` rather than just round-tripped.
+    #[test]
+    fn enum_variants_are_identified_by_a_varint_discriminant_like_the_hand_rolled_macros() {
+        let mut expected_first = Vec::new();
+        VarInt(0).mc_serialize(&mut expected_first).unwrap();
+        assert_eq!(to_bytes(&TestEnum::First).unwrap(), expected_first);
+
+        let mut expected_second = Vec::new();
+        VarInt(1).mc_serialize(&mut expected_second).unwrap();
+        expected_second.extend_from_slice(&5i32.to_be_bytes());
+        assert_eq!(to_bytes(&TestEnum::Second(5)).unwrap(), expected_second);
+
+        let mut expected_third = Vec::new();
+        VarInt(2).mc_serialize(&mut expected_third).unwrap();
+        assert_eq!(to_bytes(&TestEnum::Third).unwrap(), expected_third);
+
+        assert_eq!(from_bytes::<TestEnum>(&expected_first).unwrap(), TestEnum::First);
+        assert_eq!(from_bytes::<TestEnum>(&expected_second).unwrap(), TestEnum::Second(5));
+        assert_eq!(from_bytes::<TestEnum>(&expected_third).unwrap(), TestEnum::Third);
+    }
+}
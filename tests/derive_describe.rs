@@ -0,0 +1,50 @@
+//! Checks that `#[derive(McSerialize, McDeserialize, Packet)]` describes
+//! itself the same way a hand-written `define_protocol!` definition would,
+//! since the two are meant to be interchangeable one enum at a time.
+use mcproto_rs::protocol::{Packet, ProtocolPacketField, ProtocolPacketSpec, ProtocolSpec};
+use mcproto_rs::proto_byte_enum;
+use mcproto_rs_derive::{McDeserialize, McSerialize, Packet as PacketDerive};
+
+proto_byte_enum!(DeriveTestState,
+    0x00 :: Play
+);
+
+proto_byte_enum!(DeriveTestDirection,
+    0x00 :: ServerBound
+);
+
+mcproto_rs::define_protocol!(DeriveTestIdProtocol, DeriveTestDirection, DeriveTestState, i32, DeriveTestId => {
+    Placeholder, 0x00, Play, ServerBound => DeriveTestIdPlaceholderBody { }
+});
+
+#[derive(McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+struct DeriveTestPingBody {
+    payload: i64,
+}
+
+#[derive(PacketDerive, Debug, Clone, PartialEq)]
+#[packet(id_type = "DeriveTestId", state_type = "DeriveTestState", direction_type = "DeriveTestDirection")]
+enum DeriveTestPacket {
+    #[packet(id = 0x01, state = "Play", direction = "ServerBound")]
+    Ping(DeriveTestPingBody),
+}
+
+#[test]
+fn derive_describe_matches_what_define_protocol_would_generate() {
+    let expected = ProtocolSpec {
+        name: "DeriveTestPacket".to_owned(),
+        packets: vec![ProtocolPacketSpec {
+            state: "Play".to_owned(),
+            direction: "ServerBound".to_owned(),
+            id: 0x01,
+            name: "Ping".to_owned(),
+            body_struct: "DeriveTestPingBody".to_owned(),
+            fields: vec![ProtocolPacketField {
+                name: "payload".to_owned(),
+                kind: "i64".to_owned(),
+            }],
+        }],
+    };
+
+    assert_eq!(DeriveTestPacket::describe(), expected);
+}